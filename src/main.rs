@@ -1,21 +1,31 @@
 use clap::Parser;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{self};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 use tempfile::TempDir;
 
+mod dedupe;
 mod steam_api;
 mod utils;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// Directory containing steam clips
+    /// Directory containing steam clips. May be passed multiple times to scan several
+    /// directories (or drives) in one invocation.
     #[arg(short, long, value_parser = validate_directory)]
-    directory: String,
+    directory: Vec<String>,
+
+    /// Recurse into subdirectories, exporting any clip directory found anywhere underneath.
+    #[arg(short, long, default_value_t = false)]
+    recursive: bool,
 
     /// Directory where exported clips will end up. By default will be located in the directory passed into the directory argument.
     #[arg(short, long, value_parser = validate_directory)]
@@ -24,6 +34,65 @@ struct Args {
     /// Verbose mode
     #[arg(short, long, default_value_t = false)]
     verbose: bool,
+
+    /// Number of clips to process concurrently. Defaults to the number of available CPUs.
+    #[arg(short, long)]
+    jobs: Option<usize>,
+
+    /// Ignore the on-disk app name cache and re-fetch game names from the Steam API.
+    #[arg(long, default_value_t = false)]
+    refresh_cache: bool,
+
+    /// Video codec to use when joining audio and video. `copy` stream-copies both (fastest,
+    /// default); the others re-encode the video stream to shrink the file or change format.
+    #[arg(long, value_enum, default_value = "copy")]
+    codec: Codec,
+
+    /// Constant rate factor to use when re-encoding with `--codec`. Ignored for `copy`.
+    #[arg(long)]
+    crf: Option<u32>,
+
+    /// Encoder preset to use when re-encoding with `--codec`. Ignored for `copy`.
+    #[arg(long)]
+    preset: Option<String>,
+
+    /// Keep running after the first pass, periodically rescanning for newly created clips and
+    /// exporting them as they appear. Already-exported clips are never re-exported.
+    #[arg(long, default_value_t = false)]
+    watch: bool,
+
+    /// How often to rescan for new clips in `--watch` mode, in seconds.
+    #[arg(long, default_value_t = 30)]
+    poll_interval: u64,
+
+    /// Detect duplicate clips after export using perceptual video hashing. `--dedupe` alone
+    /// reports matches; `--dedupe=remove` also deletes all but the longest clip in each group.
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "report")]
+    dedupe: Option<dedupe::DedupeMode>,
+
+    /// Maximum Hamming distance (inclusive) at which two clips are considered duplicates.
+    #[arg(long, default_value_t = 10)]
+    tolerance: u32,
+
+    /// How to join a clip's video and audio. `concat` manually concatenates the chunk files
+    /// before merging (slower, but the more reliable default); `mpd` feeds the clip's
+    /// `session.mpd` straight to ffmpeg's DASH demuxer in one pass.
+    #[arg(long, value_enum, default_value = "concat")]
+    mode: ExportMode,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug, PartialEq, Eq)]
+enum Codec {
+    Copy,
+    H264,
+    Hevc,
+    Av1,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug, PartialEq, Eq)]
+enum ExportMode {
+    Concat,
+    Mpd,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -35,48 +104,186 @@ struct AppDetails {
 const INIT_VIDEO_FILE: &str = "init-stream0.m4s";
 const INIT_AUDIO_FILE: &str = "init-stream1.m4s";
 
+/// Per-run export settings threaded down to each worker, bundled so that the worker pool and
+/// per-clip export functions don't need a long, error-prone positional argument list.
+struct ExportOptions<'a> {
+    output_dir: &'a Option<PathBuf>,
+    cache: &'a Mutex<steam_api::AppNameCache>,
+    codec: &'a Codec,
+    crf: Option<u32>,
+    preset: &'a Option<String>,
+    mode: &'a ExportMode,
+}
+
 fn main() {
     let args = Args::parse();
-    let directory_path = Path::new(args.directory.as_str());
     let output_path = args
         .output
         .map(|output_dir| PathBuf::from(output_dir.as_str()));
 
-    let tmp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
-    println!("Creating temp directory in: {:?}", tmp_dir.path());
-
-    // TODO: validate we're in the right directory with the right subdirectories
-    match get_subdirectories(directory_path) {
-        Ok(subdirectories) => {
-            println!("Processing {} clips...", subdirectories.len());
+    let cache = Mutex::new(if args.refresh_cache {
+        steam_api::AppNameCache::new()
+    } else {
+        steam_api::load_cache()
+    });
+
+    let shutdown = Arc::new(AtomicBool::new(false));
+    if args.watch {
+        let shutdown = Arc::clone(&shutdown);
+        if let Err(error) = ctrlc::set_handler(move || {
+            println!("Received interrupt, finishing in-flight clips before stopping...");
+            shutdown.store(true, Ordering::SeqCst);
+        }) {
+            println!("Failed to set Ctrl-C handler: {}", error);
+        }
+    }
 
-            for directory in subdirectories {
-                cleanup(&tmp_dir); // Just in case there's hanging temp files
-                export_clip_at_directory(directory, &output_path, &tmp_dir);
+    let mut processed = HashSet::new();
+    let mut exported_clips = Vec::new();
+
+    loop {
+        // TODO: validate we're in the right directory with the right subdirectories
+        let mut subdirectories = Vec::new();
+        for directory in &args.directory {
+            match get_subdirectories(Path::new(directory.as_str()), args.recursive) {
+                Ok(found) => subdirectories.extend(found),
+                Err(error) => {
+                    println!("Error fetching subdirectories for {}: {}", directory, error)
+                }
             }
+        }
 
-            match output_path {
-                Some(path) => println!(
-                    "Done! Your clips have been saved in {}",
-                    path.to_str().unwrap_or_default()
-                ),
-                None => println!("Done!"),
+        let new_directories: Vec<String> = subdirectories
+            .into_iter()
+            .filter(|directory| processed.insert(directory.clone()))
+            .collect();
+
+        if !new_directories.is_empty() {
+            let num_workers = args
+                .jobs
+                .unwrap_or_else(default_worker_count)
+                .max(1)
+                .min(new_directories.len());
+
+            println!(
+                "Processing {} clip(s) with {} worker(s)...",
+                new_directories.len(),
+                num_workers
+            );
+
+            let options = ExportOptions {
+                output_dir: &output_path,
+                cache: &cache,
+                codec: &args.codec,
+                crf: args.crf,
+                preset: &args.preset,
+                mode: &args.mode,
             };
+
+            let results = run_worker_pool(new_directories, num_workers, &options, &shutdown);
+
+            let mut failures = Vec::new();
+            for result in results {
+                match result {
+                    Ok(destination) => exported_clips.push(destination),
+                    Err(failure) => failures.push(failure),
+                }
+            }
+
+            if failures.is_empty() {
+                match &output_path {
+                    Some(path) => println!(
+                        "Done! Your clips have been saved in {}",
+                        path.to_str().unwrap_or_default()
+                    ),
+                    None => println!("Done!"),
+                };
+            } else {
+                println!("Done, but {} clip(s) failed to export:", failures.len());
+                for (directory, error) in &failures {
+                    println!("  {}: {}", directory, error);
+                }
+            }
         }
-        Err(error) => {
-            println!(
-                "Error fetching subdirectories for {}: {}",
-                args.directory, error
-            )
+
+        if !args.watch || shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+
+        thread::sleep(Duration::from_secs(args.poll_interval));
+    }
+
+    if let Some(mode) = args.dedupe {
+        if let Err(error) = dedupe::run_dedupe(&exported_clips, args.tolerance, mode) {
+            println!("Error during duplicate detection: {}", error);
         }
     }
 }
 
-fn export_clip_at_directory(directory: String, output_dir: &Option<PathBuf>, tmp_dir: &TempDir) {
+fn default_worker_count() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// Processes `subdirectories` across `num_workers` threads, each with its own temp directory
+/// so that concurrent clips don't collide on `tmp_video.mp4`/`tmp_audio.mp4`. Returns one
+/// result per directory a worker processed; once `shutdown` is set, directories not yet started
+/// are skipped and get no result. Failures are tagged with the directory they came from.
+fn run_worker_pool(
+    subdirectories: Vec<String>,
+    num_workers: usize,
+    options: &ExportOptions,
+    shutdown: &AtomicBool,
+) -> Vec<Result<PathBuf, (String, io::Error)>> {
+    let work = Mutex::new(subdirectories.into_iter());
+    let results = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..num_workers {
+            scope.spawn(|| {
+                let tmp_dir = match tempfile::tempdir() {
+                    Ok(dir) => dir,
+                    Err(error) => {
+                        println!("Failed to create temporary directory: {}", error);
+                        return;
+                    }
+                };
+
+                loop {
+                    // Checked per-clip (not just between batches) so Ctrl-C finishes only the
+                    // in-flight clip on each worker rather than the whole remaining batch.
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    let directory = work.lock().unwrap().next();
+                    let Some(directory) = directory else {
+                        break;
+                    };
+
+                    cleanup(&tmp_dir); // Just in case there's hanging temp files
+                    let result = export_clip_at_directory(directory.clone(), &tmp_dir, options)
+                        .map_err(|error| (directory, error));
+                    results.lock().unwrap().push(result);
+                }
+            });
+        }
+    });
+
+    results.into_inner().unwrap()
+}
+
+fn export_clip_at_directory(
+    directory: String,
+    tmp_dir: &TempDir,
+    options: &ExportOptions,
+) -> io::Result<PathBuf> {
     println!("Processing directory: {:?}", directory);
 
-    let (steam_id, date, time) = utils::parse_clip_string(directory.as_str());
-    let game_name = get_game_name_from_id(steam_id);
+    let (steam_id, date, time) = utils::parse_clip_string(directory.as_str())
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+    let game_name = get_game_name_from_id(steam_id, options.cache);
 
     let video_clips_directory = validate_clip_directory(directory.as_str())
         .map(|res| res.unwrap_or_default())
@@ -85,20 +292,39 @@ fn export_clip_at_directory(directory: String, output_dir: &Option<PathBuf>, tmp
     println!("Clips directory: {}", video_clips_directory);
 
     let output_file_name = format!("{} {} {}", game_name, date, time);
-
-    concat_m4s_files(
-        Path::new(video_clips_directory.as_str()),
-        output_file_name,
-        output_dir,
-        tmp_dir,
-    );
+    let clip_dir = Path::new(video_clips_directory.as_str());
+
+    match options.mode {
+        ExportMode::Concat => concat_m4s_files(
+            clip_dir,
+            output_file_name,
+            options.output_dir,
+            tmp_dir,
+            options.codec,
+            options.crf,
+            options.preset,
+        ),
+        ExportMode::Mpd => quick_join_video_audio(clip_dir, output_file_name, options.output_dir),
+    }
 }
 
-fn get_game_name_from_id(steam_id: u64) -> String {
-    return match steam_api::get_app_details(steam_id) {
+/// Resolving a name always consults the shared in-memory map first — even with
+/// `--refresh-cache` — so once a game's name is resolved, later clips from that game reuse it
+/// instead of calling the Steam API. The lock is not held during the fetch, so workers that miss
+/// the map at the same time each call the API, and failed lookups (the 'clip' fallback) are not
+/// cached. `--refresh-cache` instead controls whether `main` seeds that map from the on-disk
+/// cache or starts it empty.
+fn get_game_name_from_id(steam_id: u64, cache: &Mutex<steam_api::AppNameCache>) -> String {
+    let cache_key = steam_id.to_string();
+
+    if let Some(name) = cache.lock().unwrap().get(&cache_key) {
+        return name.clone();
+    }
+
+    let game_name = match steam_api::get_app_details(steam_id) {
         Ok(app_details) => app_details
             .properties
-            .get(&steam_id.to_string())
+            .get(&cache_key)
             .and_then(|game_details| game_details.get("data"))
             .and_then(|game_data| game_data.get("name"))
             .and_then(|name| Some(sanitize_filename::sanitize(name.to_string())))
@@ -117,6 +343,14 @@ fn get_game_name_from_id(steam_id: u64) -> String {
             "clip".to_string() // default to "clip" in the filename
         }
     };
+
+    if game_name != "clip" {
+        let mut cache = cache.lock().unwrap();
+        cache.insert(cache_key, game_name.clone());
+        steam_api::save_cache(&cache);
+    }
+
+    game_name
 }
 
 fn validate_directory(path: &str) -> Result<String, String> {
@@ -146,16 +380,46 @@ fn validate_clip_directory(clip_path_str: &str) -> io::Result<Option<String>> {
     Ok(None)
 }
 
-fn get_subdirectories(clips_directory: &Path) -> io::Result<Vec<String>> {
+fn get_subdirectories(clips_directory: &Path, recursive: bool) -> io::Result<Vec<String>> {
     let mut subdirectories = Vec::new();
-    for entry in fs::read_dir(clips_directory)? {
+    collect_clip_directories(clips_directory, recursive, &mut subdirectories)?;
+    Ok(subdirectories)
+}
+
+/// Walks `dir` looking for clip directories (a `clip_*` folder with a `video/bg_*` subfolder).
+/// When `recursive` is set, also descends into non-clip directories so a user can point the
+/// tool at an entire Steam `userdata` tree instead of a single clips folder.
+fn collect_clip_directories(
+    dir: &Path,
+    recursive: bool,
+    found: &mut Vec<String>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
         let path = entry?.path();
-        if path.is_dir() {
-            subdirectories.push(path.to_string_lossy().to_string());
+        if !path.is_dir() {
+            continue;
+        }
+
+        if is_clip_directory(&path) {
+            found.push(path.to_string_lossy().to_string());
+        } else if recursive {
+            collect_clip_directories(&path, recursive, found)?;
         }
     }
 
-    Ok(subdirectories)
+    Ok(())
+}
+
+fn is_clip_directory(path: &Path) -> bool {
+    let looks_like_clip = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .map_or(false, |s| s.starts_with("clip_"));
+
+    looks_like_clip
+        && validate_clip_directory(path.to_string_lossy().as_ref())
+            .map(|res| res.is_some())
+            .unwrap_or(false)
 }
 
 fn concat_m4s_files(
@@ -163,7 +427,10 @@ fn concat_m4s_files(
     output_file_name: String,
     output_dir: &Option<PathBuf>,
     tmp_dir: &TempDir,
-) -> io::Result<()> {
+    codec: &Codec,
+    crf: Option<u32>,
+    preset: &Option<String>,
+) -> io::Result<PathBuf> {
     println!("Starting concat...");
     let init_video_file_path = dir.join(INIT_VIDEO_FILE);
     let init_audio_file_path = dir.join(INIT_AUDIO_FILE);
@@ -171,11 +438,11 @@ fn concat_m4s_files(
     if init_video_file_path.exists() && init_audio_file_path.exists() {
         concat_video_files(init_video_file_path, dir, &tmp_dir)?;
         concat_audio_files(init_audio_file_path, dir, &tmp_dir)?;
-        join_video_audio(&tmp_dir, output_file_name, output_dir)?;
+        let destination = join_video_audio(&tmp_dir, output_file_name, output_dir, codec, crf, preset)?;
 
         cleanup(&tmp_dir);
 
-        Ok(())
+        Ok(destination)
     } else {
         return Err(io::Error::new(
             io::ErrorKind::NotFound,
@@ -274,7 +541,10 @@ fn join_video_audio(
     tmp_dir: &TempDir,
     output_file_name: String,
     output_dir: &Option<PathBuf>,
-) -> io::Result<()> {
+    codec: &Codec,
+    crf: Option<u32>,
+    preset: &Option<String>,
+) -> io::Result<PathBuf> {
     println!("Merging using ffmpeg...");
 
     let destination = match output_dir {
@@ -284,23 +554,17 @@ fn join_video_audio(
 
     println!("Destination file: {:?}", destination);
 
-    let mut command = Command::new("ffmpeg")
+    let mut command = Command::new("ffmpeg");
+    command
         .arg("-i")
         .arg(tmp_dir.path().join("tmp_video.mp4"))
         .arg("-i")
-        .arg(tmp_dir.path().join("tmp_audio.mp4"))
-        .arg("-c")
-        .arg("copy")
-        // Extra commands to experiment with later
-        // .arg("-c:v")
-        // .arg("libx265")
-        // .arg("-vtag")
-        // .arg("hvc1")
-        // .arg("-c:a")
-        // .arg("copy")
-        // .arg("-crf")
-        // .arg("18")
-        .arg(destination)
+        .arg(tmp_dir.path().join("tmp_audio.mp4"));
+
+    add_codec_args(&mut command, codec, crf, preset);
+
+    let mut command = command
+        .arg(&destination)
         .stdout(Stdio::piped())
         .spawn()?;
 
@@ -318,20 +582,76 @@ fn join_video_audio(
         ));
     }
 
-    Ok(())
+    Ok(destination)
+}
+
+/// Appends the ffmpeg flags for `codec` to `command`. `copy` (the default) stream-copies both
+/// tracks to preserve the current fast behavior; the other codecs re-encode the video stream,
+/// applying `crf`/`preset` when given.
+fn add_codec_args(command: &mut Command, codec: &Codec, crf: Option<u32>, preset: &Option<String>) {
+    match codec {
+        Codec::Copy => {
+            command.arg("-c").arg("copy");
+        }
+        Codec::H264 => {
+            command.arg("-c:v").arg("libx264");
+            if let Some(crf) = crf {
+                command.arg("-crf").arg(crf.to_string());
+            }
+            if let Some(preset) = preset {
+                command.arg("-preset").arg(preset);
+            }
+            command.arg("-c:a").arg("aac");
+        }
+        Codec::Hevc => {
+            command
+                .arg("-c:v")
+                .arg("libx265")
+                .arg("-vtag")
+                .arg("hvc1");
+            if let Some(crf) = crf {
+                command.arg("-crf").arg(crf.to_string());
+            }
+            if let Some(preset) = preset {
+                command.arg("-preset").arg(preset);
+            }
+            command.arg("-c:a").arg("aac");
+        }
+        Codec::Av1 => {
+            command.arg("-c:v").arg("libsvtav1");
+            if let Some(crf) = crf {
+                command.arg("-crf").arg(crf.to_string());
+            }
+            if let Some(preset) = preset {
+                command.arg("-preset").arg(preset);
+            }
+            command.arg("-c:a").arg("aac");
+        }
+    }
 }
 
 // https://y.tsutsumi.io/reading-steam-game-recordings
 // However seems to have issues - concatting each file works better for me
-fn quick_join_video_audio(path: &Path) -> io::Result<()> {
+fn quick_join_video_audio(
+    dir: &Path,
+    output_file_name: String,
+    output_dir: &Option<PathBuf>,
+) -> io::Result<PathBuf> {
     println!("Merging quickly using ffmpeg...");
 
+    let destination = match output_dir {
+        Some(dir) => dir.join(output_file_name).with_extension("mp4"),
+        None => PathBuf::from(output_file_name).with_extension("mp4"),
+    };
+
+    println!("Destination file: {:?}", destination);
+
     let mut command = Command::new("ffmpeg")
         .arg("-i")
-        .arg(path.join("session.mpd"))
+        .arg(dir.join("session.mpd"))
         .arg("-c")
         .arg("copy")
-        .arg("output.mp4")
+        .arg(&destination)
         .stdout(Stdio::piped())
         .spawn()?;
 
@@ -349,7 +669,7 @@ fn quick_join_video_audio(path: &Path) -> io::Result<()> {
         ));
     }
 
-    Ok(())
+    Ok(destination)
 }
 
 fn cleanup(tmp_dir: &TempDir) {
@@ -1,4 +1,7 @@
 use reqwest::blocking::Client;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
 
 use crate::AppDetails;
 
@@ -16,3 +19,54 @@ pub fn get_app_details(steam_id: u64) -> Result<AppDetails, reqwest::Error> {
     let app_details = response.json()?;
     Ok(app_details)
 }
+
+/// `steam_id -> game name` cache, persisted to disk so that repeated runs over clips from the
+/// same games don't repeatedly hit the rate-limited appdetails endpoint.
+pub type AppNameCache = HashMap<String, String>;
+
+fn cache_path() -> PathBuf {
+    let cache_home = std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    cache_home
+        .join(".cache")
+        .join("steamclipexporter")
+        .join("appdetails.json")
+}
+
+/// Loads the on-disk app name cache, if present. Missing or unparseable caches are treated as
+/// empty rather than errors, since a cold cache is the normal first-run state.
+pub fn load_cache() -> AppNameCache {
+    fs::read_to_string(cache_path())
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Writes `cache` back to disk, first merging it over whatever is already there. `--refresh-cache`
+/// runs seed their in-memory map empty (so every lookup is a fresh fetch), but that map only ever
+/// covers games touched in *this* run — writing it out verbatim would truncate the on-disk cache
+/// down to just those entries. Loading and merging means a `--refresh-cache` run only overwrites
+/// the keys it actually re-fetched, leaving every other cached game alone.
+pub fn save_cache(cache: &AppNameCache) {
+    let mut merged = load_cache();
+    merged.extend(cache.iter().map(|(steam_id, name)| (steam_id.clone(), name.clone())));
+
+    let path = cache_path();
+    if let Some(parent) = path.parent() {
+        if let Err(error) = fs::create_dir_all(parent) {
+            println!("Failed to create cache directory {:?}: {}", parent, error);
+            return;
+        }
+    }
+
+    match serde_json::to_string_pretty(&merged) {
+        Ok(contents) => {
+            if let Err(error) = fs::write(&path, contents) {
+                println!("Failed to write app details cache to {:?}: {}", path, error);
+            }
+        }
+        Err(error) => println!("Failed to serialize app details cache: {}", error),
+    }
+}
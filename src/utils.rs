@@ -20,15 +20,37 @@ pub fn sort_chunks(chunk_files: &mut Vec<PathBuf>) {
     });
 }
 
-pub fn parse_clip_string(clip_string: &str) -> (u64, u64, u64) {
+/// Parses a `clip_<steam_id>_<date>_<time>` directory name into its parts; the leading `clip_`
+/// is stripped when present but not required. Returns `Err` with a human-readable message
+/// (rather than panicking) when the rest is not three `_`-separated numbers, so a
+/// single malformed directory can be reported as a per-clip failure instead of taking down the
+/// whole worker pool.
+pub fn parse_clip_string(clip_string: &str) -> Result<(u64, u64, u64), String> {
     let path = Path::new(clip_string);
-    let last_part = path.file_name().unwrap().to_str().unwrap();
+    let last_part = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("{:?} has no file name", clip_string))?;
     let trimmed_part = last_part.trim_start_matches("clip_");
     let parts: Vec<&str> = trimmed_part.split('_').collect();
     println!("parts: {:?}", parts);
-    let clip_number = parts[0].parse().unwrap();
-    let date = parts[1].parse().unwrap();
-    let time = parts[2].parse().unwrap();
 
-    (clip_number, date, time)
+    if parts.len() != 3 {
+        return Err(format!(
+            "expected \"clip_<steam_id>_<date>_<time>\", got {:?}",
+            last_part
+        ));
+    }
+
+    let clip_number = parts[0]
+        .parse()
+        .map_err(|_| format!("{:?} is not a valid steam_id", parts[0]))?;
+    let date = parts[1]
+        .parse()
+        .map_err(|_| format!("{:?} is not a valid date", parts[1]))?;
+    let time = parts[2]
+        .parse()
+        .map_err(|_| format!("{:?} is not a valid time", parts[2]))?;
+
+    Ok((clip_number, date, time))
 }